@@ -0,0 +1,226 @@
+//! Accounts and the account signing key.
+use crate::api::{ApiAccount, ApiDirectory, ApiIdentifier, ApiNewOrder, ApiOrder};
+use crate::error;
+use crate::order::{Identifier, NewOrder, Order};
+use crate::req::req_expect_header;
+use crate::trans::Transport;
+use crate::util::read_json;
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::ecdsa::EcdsaSig;
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::sign::Signer;
+use serde_json::json;
+use std::sync::Arc;
+
+/// The ECDSA P-256 key pair an account (or a certificate) signs ACME requests with.
+pub struct AcmeKey {
+    private_key: PKey<Private>,
+}
+
+impl Clone for AcmeKey {
+    fn clone(&self) -> Self {
+        AcmeKey {
+            private_key: self.private_key.clone(),
+        }
+    }
+}
+
+impl AcmeKey {
+    /// Generate a fresh P-256 key, as used for new accounts.
+    pub fn new() -> Result<AcmeKey, error::Error> {
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+        let ec_key = EcKey::generate(&group)?;
+        Ok(AcmeKey {
+            private_key: PKey::from_ec_key(ec_key)?,
+        })
+    }
+
+    /// Load a previously generated key from a PEM-encoded private key.
+    pub fn from_pem(pem: &[u8]) -> Result<AcmeKey, error::Error> {
+        let private_key = PKey::private_key_from_pem(pem)?;
+        Ok(AcmeKey { private_key })
+    }
+
+    pub(crate) fn signing_algorithm(&self) -> &'static str {
+        "ES256"
+    }
+
+    /// The public half of this key as a JWK, as embedded in `jwk`-signed JWS.
+    pub(crate) fn to_public_jwk(&self) -> Result<serde_json::Value, error::Error> {
+        let ec_key = self.private_key.ec_key()?;
+        let mut ctx = BigNumContext::new()?;
+        let mut x = BigNum::new()?;
+        let mut y = BigNum::new()?;
+        ec_key
+            .public_key()
+            .affine_coordinates_gfp(ec_key.group(), &mut x, &mut y, &mut ctx)?;
+        Ok(json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": crate::util::base64url(&x.to_vec()),
+            "y": crate::util::base64url(&y.to_vec()),
+        }))
+    }
+
+    /// The [RFC 7638] JWK thumbprint of the public key, as embedded in the
+    /// key authorization for `dns-01`/`http-01` challenge proofs.
+    ///
+    /// [RFC 7638]: https://tools.ietf.org/html/rfc7638
+    pub(crate) fn thumbprint(&self) -> Result<String, error::Error> {
+        let jwk = self.to_public_jwk()?;
+        // RFC 7638 requires the member names in lexicographic order with no
+        // insignificant whitespace; for an EC JWK that's crv, kty, x, y.
+        let canonical = format!(
+            r#"{{"crv":"{}","kty":"{}","x":"{}","y":"{}"}}"#,
+            jwk["crv"].as_str().unwrap_or_default(),
+            jwk["kty"].as_str().unwrap_or_default(),
+            jwk["x"].as_str().unwrap_or_default(),
+            jwk["y"].as_str().unwrap_or_default(),
+        );
+        let digest = openssl::hash::hash(MessageDigest::sha256(), canonical.as_bytes())?;
+        Ok(crate::util::base64url(&digest))
+    }
+
+    /// Sign `data`, returning the raw (not DER) `r || s` ECDSA signature JWS expects.
+    pub(crate) fn sign(&self, data: &[u8]) -> Result<Vec<u8>, error::Error> {
+        let mut signer = Signer::new(MessageDigest::sha256(), &self.private_key)?;
+        signer.update(data)?;
+        let der_sig = signer.sign_to_vec()?;
+        let sig = EcdsaSig::from_der(&der_sig)?;
+
+        let r = sig.r().to_vec();
+        let s = sig.s().to_vec();
+        let mut raw = vec![0u8; 64];
+        raw[32 - r.len()..32].copy_from_slice(&r);
+        raw[64 - s.len()..64].copy_from_slice(&s);
+        Ok(raw)
+    }
+}
+
+/// State shared by an [`Account`] and the [`crate::order`] façades it creates.
+pub(crate) struct AccountInner {
+    pub(crate) transport: Transport,
+    pub(crate) api_directory: ApiDirectory,
+}
+
+/// A registered ACME account.
+#[derive(Clone)]
+pub struct Account {
+    inner: Arc<AccountInner>,
+    api_account: ApiAccount,
+}
+
+impl Account {
+    pub(crate) fn new(
+        transport: Transport,
+        api_account: ApiAccount,
+        api_directory: ApiDirectory,
+    ) -> Account {
+        Account {
+            inner: Arc::new(AccountInner {
+                transport,
+                api_directory,
+            }),
+            api_account,
+        }
+    }
+
+    /// Create a new order for `primary_name`, with optional subject alt names.
+    pub async fn new_order(
+        &self,
+        primary_name: &str,
+        alt_names: &[&str],
+    ) -> Result<NewOrder, error::Error> {
+        let identifiers: Vec<Identifier> = std::iter::once(primary_name)
+            .chain(alt_names.iter().copied())
+            .map(|name| Identifier::Dns(name.to_string()))
+            .collect();
+        self.new_order_with_identifiers(&identifiers).await
+    }
+
+    /// Create a new order for arbitrary [`Identifier`]s, e.g. to mix DNS
+    /// names with [RFC 8738] IP addresses. [`Account::new_order`] is a
+    /// convenience wrapper over this for the common all-DNS case.
+    ///
+    /// [RFC 8738]: https://tools.ietf.org/html/rfc8738
+    /// [`Account::new_order`]: struct.Account.html#method.new_order
+    pub async fn new_order_with_identifiers(
+        &self,
+        identifiers: &[Identifier],
+    ) -> Result<NewOrder, error::Error> {
+        let payload = ApiNewOrder {
+            identifiers: identifiers.iter().map(ApiIdentifier::from).collect(),
+        };
+
+        let res = self
+            .inner
+            .transport
+            .call(&self.inner.api_directory.newOrder, &payload)
+            .await?;
+        let url = req_expect_header(&res, "location")?;
+        let api_order: ApiOrder = read_json(res).await?;
+
+        Ok(NewOrder {
+            order: Order::new(&self.inner, api_order, url),
+        })
+    }
+
+    /// Rotate this account's signing key ([RFC 8555 section 7.3.5], `keyChange`).
+    ///
+    /// Builds the inner new-key-signed JWS via [`crate::dir::key_change_jws`],
+    /// wraps it as the payload of an outer call signed with the *current*
+    /// account key, and on success swaps the stored [`AcmeKey`] so
+    /// subsequent calls sign with `new_key`.
+    ///
+    /// [RFC 8555 section 7.3.5]: https://tools.ietf.org/html/rfc8555#section-7.3.5
+    pub async fn change_key(&self, new_key: AcmeKey) -> Result<(), error::Error> {
+        let kid = self.inner.transport.key_id().await.ok_or_else(|| {
+            error::Error::LetsEncryptError("account has no key id yet".to_string())
+        })?;
+        let key_change_url = self.inner.api_directory.keyChange.clone();
+        let old_key = self.inner.transport.signing_key().await;
+
+        let inner_jws = crate::dir::key_change_jws(&key_change_url, &kid, &old_key, &new_key)?;
+        self.inner.transport.call(&key_change_url, &inner_jws).await?;
+
+        self.inner.transport.set_signing_key(new_key).await;
+        Ok(())
+    }
+
+    /// Access the underlying JSON object for debugging.
+    pub fn api_account(&self) -> &ApiAccount {
+        &self.api_account
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dir::{Directory, DirectoryUrl};
+
+    #[tokio::test]
+    async fn test_change_key() -> Result<(), error::Error> {
+        let server = crate::test::with_directory_server();
+        let url = DirectoryUrl::Other(&server.dir_url);
+        let dir = Directory::from_url(url).await?;
+        let acc = dir
+            .register_account(vec!["mailto:foo@bar.com".to_string()])
+            .await?;
+
+        let new_key = AcmeKey::new()?;
+        let new_jwk = new_key.to_public_jwk()?;
+
+        acc.change_key(new_key).await?;
+
+        // the account's signing key is swapped in place...
+        let current_jwk = acc.inner.transport.signing_key().await.to_public_jwk()?;
+        assert_eq!(current_jwk, new_jwk);
+
+        // ...so later calls succeed, signed with the new key.
+        let _ = acc.new_order("acmetest.example.com", &[]).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+//! JSON shapes of the ACME API (RFC 8555), (de)serialized mostly as-is.
+use crate::error;
+use crate::order::{Identifier, OrderStatus};
+use serde::{Deserialize, Serialize};
+
+/// The ACME directory object: the well-known resource URLs for an ACME CA.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(non_snake_case)]
+pub struct ApiDirectory {
+    pub newNonce: String,
+    pub newAccount: String,
+    pub newOrder: String,
+    pub revokeCert: String,
+    pub keyChange: String,
+    pub renewalInfo: Option<String>,
+}
+
+/// The account object sent to and returned from `newAccount`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct ApiAccount {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contact: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub termsOfServiceAgreed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub externalAccountBinding: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orders: Option<String>,
+}
+
+/// A single ACME identifier (DNS name or IP address), as it appears on the wire.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiIdentifier {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub value: String,
+}
+
+impl ApiIdentifier {
+    pub(crate) fn to_identifier(&self) -> Result<Identifier, error::Error> {
+        match self.type_.as_str() {
+            "ip" => {
+                let addr = self.value.parse().map_err(|_| {
+                    error::Error::LetsEncryptError(format!(
+                        "ACME server returned an invalid ip identifier: {}",
+                        self.value
+                    ))
+                })?;
+                Ok(Identifier::Ip(addr))
+            }
+            _ => Ok(Identifier::Dns(self.value.clone())),
+        }
+    }
+}
+
+impl From<&Identifier> for ApiIdentifier {
+    fn from(identifier: &Identifier) -> Self {
+        ApiIdentifier {
+            type_: identifier.acme_type().to_string(),
+            value: identifier.value(),
+        }
+    }
+}
+
+/// Payload for `newOrder`.
+#[derive(Debug, Serialize)]
+pub struct ApiNewOrder {
+    pub identifiers: Vec<ApiIdentifier>,
+}
+
+/// The order object, as returned by `newOrder` and by polling the order URL.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiOrder {
+    pub status: OrderStatus,
+    pub identifiers: Vec<ApiIdentifier>,
+    pub authorizations: Option<Vec<String>>,
+    pub finalize: String,
+    pub certificate: Option<String>,
+}
+
+impl ApiOrder {
+    pub(crate) fn identifiers(&self) -> Result<Vec<Identifier>, error::Error> {
+        self.identifiers
+            .iter()
+            .map(ApiIdentifier::to_identifier)
+            .collect()
+    }
+}
+
+/// The authorization object fetched from one of an order's `authorizations` URLs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiAuth {
+    pub status: String,
+    pub identifier: ApiIdentifier,
+    #[serde(default)]
+    pub challenges: Vec<ApiChallenge>,
+}
+
+impl ApiAuth {
+    pub(crate) fn identifier(&self) -> Result<Identifier, error::Error> {
+        self.identifier.to_identifier()
+    }
+}
+
+/// A single challenge offered by an authorization.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiChallenge {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub url: String,
+    pub token: String,
+    #[allow(dead_code)]
+    pub status: String,
+}
+
+/// Payload for the order's `finalize` URL: the CSR, base64url-DER-encoded.
+#[derive(Debug, Serialize)]
+pub struct ApiFinalize {
+    pub csr: String,
+}
+
+/// The empty JSON string `""` that RFC 8555 section 6.3 requires as the
+/// payload of a POST-as-GET request.
+pub(crate) struct ApiEmptyString;
+
+impl Serialize for ApiEmptyString {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str("")
+    }
+}
@@ -0,0 +1,205 @@
+//! Certificate signing requests and issued certificates.
+use crate::error;
+use crate::order::Identifier;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::stack::Stack;
+use openssl::x509::extension::SubjectAlternativeName;
+use openssl::x509::{X509Req, X509ReqBuilder, X509};
+use openssl::hash::MessageDigest;
+
+/// Generate a fresh P-256 key pair, e.g. for use in a CSR.
+pub fn create_p256_key() -> Result<PKey<Private>, error::Error> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let ec_key = EcKey::generate(&group)?;
+    Ok(PKey::from_ec_key(ec_key)?)
+}
+
+/// Build the CSR for `identifiers`, routing DNS names to `dNSName` SAN
+/// entries and IP addresses ([RFC 8738]) to `iPAddress` entries.
+///
+/// [RFC 8738]: https://tools.ietf.org/html/rfc8738
+pub(crate) fn create_csr(
+    private_key: &PKey<Private>,
+    identifiers: &[Identifier],
+) -> Result<X509Req, error::Error> {
+    let mut builder = X509ReqBuilder::new()?;
+    builder.set_pubkey(private_key)?;
+
+    let mut san = SubjectAlternativeName::new();
+    for identifier in identifiers {
+        match identifier {
+            Identifier::Dns(name) => san.dns(name),
+            Identifier::Ip(addr) => san.ip(&addr.to_string()),
+        };
+    }
+    let ctx = builder.x509v3_context(None);
+    let extension = san.build(&ctx)?;
+    let mut extensions = Stack::new()?;
+    extensions.push(extension)?;
+    builder.add_extensions(&extensions)?;
+
+    builder.sign(private_key, MessageDigest::sha256())?;
+    Ok(builder.build())
+}
+
+/// A downloaded certificate and the private key it was issued for.
+pub struct Certificate {
+    private_key: String,
+    certificate: String,
+}
+
+impl Certificate {
+    pub(crate) fn new(private_key: String, certificate: String) -> Certificate {
+        Certificate {
+            private_key,
+            certificate,
+        }
+    }
+
+    /// The private key, as PEM.
+    pub fn private_key(&self) -> &str {
+        &self.private_key
+    }
+
+    /// The certificate, as PEM.
+    pub fn certificate(&self) -> &str {
+        &self.certificate
+    }
+
+    /// The certificate's `notAfter` expiry.
+    pub fn expiry(&self) -> Result<DateTime<Utc>, error::Error> {
+        let x509 = X509::from_pem(self.certificate.as_bytes())?;
+        let not_after = x509.not_after().to_string();
+        let naive = NaiveDateTime::parse_from_str(&not_after, "%h %e %H:%M:%S %Y GMT")?;
+        Ok(DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
+    }
+
+    /// Days left until the certificate expires, possibly negative.
+    pub fn valid_days_left(&self) -> Result<i64, error::Error> {
+        Ok((self.expiry()? - Utc::now()).num_days())
+    }
+
+    /// The [ACME Renewal Information] `certID` for this certificate: the
+    /// base64url DER `OCTET STRING` encoding of the Authority Key
+    /// Identifier `keyIdentifier`, joined with a `.` to the base64url DER
+    /// `INTEGER` encoding of the certificate's serial number.
+    ///
+    /// [ACME Renewal Information]: https://datatracker.ietf.org/doc/draft-ietf-acme-ari/
+    pub fn ari_cert_id(&self) -> Result<String, error::Error> {
+        let x509 = X509::from_pem(self.certificate.as_bytes())?;
+        let aki = x509.authority_key_id().ok_or_else(|| {
+            error::Error::LetsEncryptError(
+                "certificate has no Authority Key Identifier, required for ARI".to_string(),
+            )
+        })?;
+        let serial = x509.serial_number().to_bn()?.to_vec();
+
+        Ok(format!(
+            "{}.{}",
+            crate::util::base64url(&der_octet_string(aki.as_slice())),
+            crate::util::base64url(&der_integer(&serial)),
+        ))
+    }
+}
+
+fn der_len(len: usize) -> Vec<u8> {
+    if len < 0x80 {
+        return vec![len as u8];
+    }
+    let bytes = len.to_be_bytes();
+    let first_nonzero = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len() - 1);
+    let len_bytes = &bytes[first_nonzero..];
+    let mut out = vec![0x80 | len_bytes.len() as u8];
+    out.extend_from_slice(len_bytes);
+    out
+}
+
+fn der_octet_string(content: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x04];
+    out.extend(der_len(content.len()));
+    out.extend_from_slice(content);
+    out
+}
+
+fn der_integer(magnitude: &[u8]) -> Vec<u8> {
+    // A BIGNUM's big-endian bytes already drop leading zeros, but DER needs
+    // a leading 0x00 byte reinstated when the high bit is set, or the value
+    // would be read back as negative.
+    let mut content = magnitude.to_vec();
+    if content.is_empty() {
+        content.push(0);
+    } else if content[0] & 0x80 != 0 {
+        content.insert(0, 0);
+    }
+    let mut out = vec![0x02];
+    out.extend(der_len(content.len()));
+    out.extend_from_slice(&content);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::{BigNum, MsbOption};
+    use openssl::hash::MessageDigest as Md;
+    use openssl::x509::extension::{AuthorityKeyIdentifier, SubjectKeyIdentifier};
+    use openssl::x509::X509Builder;
+
+    fn self_signed_with_aki() -> Result<X509, error::Error> {
+        let key = create_p256_key()?;
+        let mut builder = X509Builder::new()?;
+        builder.set_version(2)?;
+        let mut serial = BigNum::new()?;
+        serial.rand(64, MsbOption::MAYBE_ZERO, false)?;
+        let serial = serial.to_asn1_integer()?;
+        builder.set_serial_number(&serial)?;
+        builder.set_pubkey(&key)?;
+        let not_before = Asn1Time::days_from_now(0)?;
+        builder.set_not_before(&not_before)?;
+        let not_after = Asn1Time::days_from_now(365)?;
+        builder.set_not_after(&not_after)?;
+
+        let ski = SubjectKeyIdentifier::new().build(&builder.x509v3_context(None, None))?;
+        builder.append_extension(ski)?;
+        let aki = AuthorityKeyIdentifier::new()
+            .keyid(true)
+            .build(&builder.x509v3_context(None, None))?;
+        builder.append_extension(aki)?;
+
+        builder.sign(&key, Md::sha256())?;
+        Ok(builder.build())
+    }
+
+    #[test]
+    fn test_create_csr_has_san_for_dns_and_ip() -> Result<(), error::Error> {
+        let key = create_p256_key()?;
+        let identifiers = vec![
+            Identifier::Dns("example.com".to_string()),
+            Identifier::Ip("203.0.113.1".parse().unwrap()),
+        ];
+        let csr = create_csr(&key, &identifiers)?;
+        let pem = csr.to_pem()?;
+        assert!(!pem.is_empty());
+        let text = String::from_utf8(csr.to_text()?).unwrap();
+        assert!(text.contains("DNS:example.com"));
+        assert!(text.contains("IP Address:203.0.113.1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ari_cert_id_shape() -> Result<(), error::Error> {
+        let x509 = self_signed_with_aki()?;
+        let pem = String::from_utf8(x509.to_pem()?).unwrap();
+        let cert = Certificate::new(String::new(), pem);
+        let cert_id = cert.ari_cert_id()?;
+        let parts: Vec<&str> = cert_id.split('.').collect();
+        assert_eq!(parts.len(), 2);
+        assert!(!parts[0].is_empty());
+        assert!(!parts[1].is_empty());
+        Ok(())
+    }
+}
@@ -1,13 +1,21 @@
 //
 use std::sync::Arc;
 
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use openssl::x509::X509;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
 use crate::{
     acc::AcmeKey,
     api::{ApiAccount, ApiDirectory},
     error,
     req::{req_expect_header, req_get},
     trans::{NoncePool, Transport},
-    util::read_json,
+    util::{base64url, read_json},
     Account,
 };
 
@@ -48,7 +56,7 @@ impl Directory {
     /// Create a directory over a persistence implementation and directory url.
     pub async fn from_url(url: DirectoryUrl<'_>) -> Result<Directory, error::Error> {
         let dir_url = url.to_url();
-        let res = req_get(&dir_url).await?;
+        let res = req_get(dir_url).await?;
         let api_directory: ApiDirectory = serde_json::from_str(&res.body)?;
         let nonce_pool = Arc::new(NoncePool::new(&api_directory.newNonce).await);
         Ok(Directory {
@@ -59,7 +67,31 @@ impl Directory {
 
     pub async fn register_account(&self, contact: Vec<String>) -> Result<Account, error::Error> {
         let acme_key = AcmeKey::new()?;
-        self.upsert_account(acme_key, contact).await
+        self.upsert_account(acme_key, contact, None).await
+    }
+
+    /// Register an account with an ACME CA that requires [External Account
+    /// Binding] (ZeroSSL, Google Trust Services, Sectigo, ...).
+    ///
+    /// `eab_kid` and `eab_hmac_key` are the key id and MAC key the CA hands
+    /// out when you create the account out-of-band (usually from their web
+    /// console or API).
+    ///
+    /// [External Account Binding]: https://tools.ietf.org/html/rfc8555#section-7.3.4
+    pub async fn register_account_with_eab(
+        &self,
+        contact: Vec<String>,
+        eab_kid: &str,
+        eab_hmac_key: &[u8],
+    ) -> Result<Account, error::Error> {
+        let acme_key = AcmeKey::new()?;
+        let eab = eab_jws(
+            &self.api_directory.newAccount,
+            eab_kid,
+            eab_hmac_key,
+            &acme_key.to_public_jwk()?,
+        )?;
+        self.upsert_account(acme_key, contact, Some(eab)).await
     }
 
     pub async fn load_account(
@@ -68,13 +100,14 @@ impl Directory {
         contact: Vec<String>,
     ) -> Result<Account, error::Error> {
         let acme_key = AcmeKey::from_pem(pem.as_bytes())?;
-        self.upsert_account(acme_key, contact).await
+        self.upsert_account(acme_key, contact, None).await
     }
 
     async fn upsert_account(
         &self,
         acme_key: AcmeKey,
         contact: Vec<String>,
+        external_account_binding: Option<serde_json::Value>,
     ) -> Result<Account, error::Error> {
         // Prepare making a call to newAccount. This is fine to do both for
         // new keys and existing. For existing the spec says to return a 200
@@ -82,10 +115,11 @@ impl Directory {
         let acc = ApiAccount {
             contact,
             termsOfServiceAgreed: Some(true),
+            externalAccountBinding: external_account_binding,
             ..Default::default()
         };
 
-        let mut transport = Transport::new(&self.nonce_pool, acme_key).await;
+        let transport = Transport::new(&self.nonce_pool, acme_key).await;
         let res = transport
             .call_jwk(&self.api_directory.newAccount, &acc)
             .await?;
@@ -104,12 +138,238 @@ impl Directory {
         ))
     }
 
+    /// Query the ACME Renewal Information (ARI) endpoint for `cert_id` and
+    /// return the CA-suggested renewal window, if the CA advertises one.
+    ///
+    /// `cert_id` is obtained from `Certificate::ari_cert_id()`. Returns
+    /// `Ok(None)` when the directory doesn't advertise a `renewalInfo`
+    /// resource, i.e. the CA doesn't support ARI.
+    pub async fn renewal_info(&self, cert_id: &str) -> Result<Option<RenewalInfo>, error::Error> {
+        let renewal_info_url = match &self.api_directory.renewalInfo {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+        let url = format!("{}/{}", renewal_info_url.trim_end_matches('/'), cert_id);
+        let res = req_get(&url).await?;
+        let info: RenewalInfo = serde_json::from_str(&res.body)?;
+        Ok(Some(info))
+    }
+
+    /// Revoke a certificate, signed with the certificate's own private key.
+    ///
+    /// Use this when the account that issued the certificate is no longer
+    /// available. If the account is still around, prefer
+    /// [`CertOrder::revoke`] which signs with the account key instead and
+    /// doesn't need the certificate's private key.
+    ///
+    /// [`CertOrder::revoke`]: ../order/struct.CertOrder.html#method.revoke
+    pub async fn revoke_certificate(
+        &self,
+        cert_pem: &str,
+        private_key_pem: &str,
+        reason: RevokeReason,
+    ) -> Result<(), error::Error> {
+        let cert_der = X509::from_pem(cert_pem.as_bytes())?.to_der()?;
+        let acme_key = AcmeKey::from_pem(private_key_pem.as_bytes())?;
+        let transport = Transport::new(&self.nonce_pool, acme_key).await;
+
+        revoke(&transport, &self.api_directory.revokeCert, &cert_der, reason).await
+    }
+
+    /// The directory's `keyChange` endpoint, used by `Account::change_key`
+    /// to perform RFC 8555 account key rollover.
+    pub fn key_change_url(&self) -> &str {
+        &self.api_directory.keyChange
+    }
+
     /// Access the underlying JSON object for debugging.
     pub fn api_directory(&self) -> &ApiDirectory {
         &self.api_directory
     }
 }
 
+/// Build the inner, new-key-signed JWS that RFC 8555 key rollover
+/// (`keyChange`) wraps as the payload of an outer, kid-signed JWS call.
+///
+/// `Account::change_key` sends the result as the payload of an outer call
+/// signed with the *current* account key via the existing
+/// `Transport::call`, and on success swaps its stored `AcmeKey` for
+/// `new_key` so subsequent calls sign with the rotated key.
+pub fn key_change_jws(
+    key_change_url: &str,
+    kid: &str,
+    old_key: &AcmeKey,
+    new_key: &AcmeKey,
+) -> Result<serde_json::Value, error::Error> {
+    let protected = json!({
+        "alg": new_key.signing_algorithm(),
+        "jwk": new_key.to_public_jwk()?,
+        "url": key_change_url,
+    });
+    let payload = json!({
+        "account": kid,
+        "oldKey": old_key.to_public_jwk()?,
+    });
+
+    let protected_b64 = base64url(protected.to_string().as_bytes());
+    let payload_b64 = base64url(payload.to_string().as_bytes());
+    let signing_input = format!("{}.{}", protected_b64, payload_b64);
+    let signature = new_key.sign(signing_input.as_bytes())?;
+
+    Ok(json!({
+        "protected": protected_b64,
+        "payload": payload_b64,
+        "signature": base64url(&signature),
+    }))
+}
+
+/// Build and send the `revokeCert` request shared by
+/// [`Directory::revoke_certificate`] and [`CertOrder::revoke`].
+///
+/// Signs with the account key (kid-based) when `transport` already has a
+/// key id, and falls back to the jwk-based signing a bare certificate key
+/// needs otherwise.
+///
+/// [`CertOrder::revoke`]: ../order/struct.CertOrder.html#method.revoke
+pub(crate) async fn revoke(
+    transport: &Transport,
+    revoke_cert_url: &str,
+    cert_der: &[u8],
+    reason: RevokeReason,
+) -> Result<(), error::Error> {
+    let payload = ApiRevokeCert {
+        certificate: base64url(cert_der),
+        reason: reason.code(),
+    };
+    if transport.has_key_id().await {
+        transport.call(revoke_cert_url, &payload).await?;
+    } else {
+        transport.call_jwk(revoke_cert_url, &payload).await?;
+    }
+    Ok(())
+}
+
+/// Standard certificate revocation reason codes (RFC 5280 section 5.3.1,
+/// as referenced by RFC 8555 section 7.6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevokeReason {
+    Unspecified,
+    KeyCompromise,
+    CaCompromise,
+    AffiliationChanged,
+    Superseded,
+    CessationOfOperation,
+    CertificateHold,
+    RemoveFromCrl,
+    PrivilegeWithdrawn,
+    AaCompromise,
+}
+
+impl RevokeReason {
+    pub(crate) fn code(self) -> u8 {
+        match self {
+            RevokeReason::Unspecified => 0,
+            RevokeReason::KeyCompromise => 1,
+            RevokeReason::CaCompromise => 2,
+            RevokeReason::AffiliationChanged => 3,
+            RevokeReason::Superseded => 4,
+            RevokeReason::CessationOfOperation => 5,
+            RevokeReason::CertificateHold => 6,
+            RevokeReason::RemoveFromCrl => 8,
+            RevokeReason::PrivilegeWithdrawn => 9,
+            RevokeReason::AaCompromise => 10,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ApiRevokeCert {
+    pub(crate) certificate: String,
+    pub(crate) reason: u8,
+}
+
+/// The CA-suggested renewal window for a certificate, as returned by the
+/// [ACME Renewal Information] (ARI) endpoint.
+///
+/// [ACME Renewal Information]: https://datatracker.ietf.org/doc/draft-ietf-acme-ari/
+#[derive(Debug, Clone, Deserialize)]
+#[allow(non_snake_case)]
+pub struct RenewalInfo {
+    suggestedWindow: RenewalWindow,
+    explanationURL: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RenewalWindow {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl RenewalInfo {
+    /// The CA-suggested `(start, end)` window in which to renew.
+    pub fn window(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        (self.suggestedWindow.start, self.suggestedWindow.end)
+    }
+
+    /// A human readable URL explaining how the window was chosen, if the CA provided one.
+    pub fn explanation_url(&self) -> Option<&str> {
+        self.explanationURL.as_deref()
+    }
+
+    /// Pick a uniformly random instant within the suggested window.
+    ///
+    /// Renewal loops should schedule against this rather than always
+    /// renewing at `start`, so that clients sharing a certificate don't
+    /// all hit the CA in the same instant.
+    pub fn random_renewal_time(&self) -> Result<DateTime<Utc>, error::Error> {
+        let (start, end) = self.window();
+        let span_ms = (end - start).num_milliseconds().max(0) as u64;
+        let offset_ms = if span_ms == 0 {
+            0
+        } else {
+            let mut buf = [0u8; 8];
+            openssl::rand::rand_bytes(&mut buf)?;
+            u64::from_be_bytes(buf) % span_ms
+        };
+        Ok(start + ChronoDuration::milliseconds(offset_ms as i64))
+    }
+}
+
+/// Build the flattened JWS that goes into `newAccount`'s
+/// `externalAccountBinding` field (RFC 8555 section 7.3.4).
+///
+/// The inner JWS is signed with the CA-issued `eab_hmac_key` using
+/// `eab_kid` as the key id, and its payload is the account key's public
+/// JWK: this is what lets the CA tie the newly generated ACME account key
+/// to the out-of-band identity it already knows about.
+fn eab_jws(
+    new_account_url: &str,
+    eab_kid: &str,
+    eab_hmac_key: &[u8],
+    jwk: &serde_json::Value,
+) -> Result<serde_json::Value, error::Error> {
+    let protected = json!({
+        "alg": "HS256",
+        "kid": eab_kid,
+        "url": new_account_url,
+    });
+    let protected_b64 = base64url(protected.to_string().as_bytes());
+    let payload_b64 = base64url(jwk.to_string().as_bytes());
+
+    let signing_input = format!("{}.{}", protected_b64, payload_b64);
+
+    let key = PKey::hmac(eab_hmac_key)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(signing_input.as_bytes())?;
+    let signature = signer.sign_to_vec()?;
+
+    Ok(json!({
+        "protected": protected_b64,
+        "payload": payload_b64,
+        "signature": base64url(&signature),
+    }))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -133,6 +393,78 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_create_account_with_eab() -> Result<(), error::Error> {
+        let server = crate::test::with_directory_server();
+        let url = DirectoryUrl::Other(&server.dir_url);
+        let dir = Directory::from_url(url).await?;
+        let _ = dir
+            .register_account_with_eab(
+                vec!["mailto:foo@bar.com".to_string()],
+                "kid-12345",
+                b"some-hmac-key-bytes",
+            )
+            .await?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_change_jws_shape() -> Result<(), error::Error> {
+        let old_key = AcmeKey::new()?;
+        let new_key = AcmeKey::new()?;
+        let jws = key_change_jws(
+            "https://example.org/acme/key-change",
+            "https://example.org/acme/acct/1",
+            &old_key,
+            &new_key,
+        )?;
+        assert!(jws.get("protected").is_some());
+        assert!(jws.get("payload").is_some());
+        assert!(jws.get("signature").is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_revoke_reason_codes() {
+        assert_eq!(RevokeReason::Unspecified.code(), 0);
+        assert_eq!(RevokeReason::KeyCompromise.code(), 1);
+        assert_eq!(RevokeReason::CessationOfOperation.code(), 5);
+        assert_eq!(RevokeReason::AaCompromise.code(), 10);
+    }
+
+    #[test]
+    fn test_renewal_info_window() -> Result<(), error::Error> {
+        let json = r#"{
+            "suggestedWindow": {
+                "start": "2026-07-27T00:00:00Z",
+                "end": "2026-07-28T00:00:00Z"
+            },
+            "explanationURL": "https://example.org/ari"
+        }"#;
+        let info: RenewalInfo = serde_json::from_str(json)?;
+        let (start, end) = info.window();
+        assert!(start < end);
+        let picked = info.random_renewal_time()?;
+        assert!(picked >= start && picked <= end);
+        assert_eq!(info.explanation_url(), Some("https://example.org/ari"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_eab_jws_shape() -> Result<(), error::Error> {
+        let jwk = json!({ "kty": "EC", "crv": "P-256", "x": "x", "y": "y" });
+        let eab = eab_jws(
+            "https://example.org/acme/new-account",
+            "kid-1",
+            b"secret",
+            &jwk,
+        )?;
+        assert!(eab.get("protected").is_some());
+        assert!(eab.get("payload").is_some());
+        assert!(eab.get("signature").is_some());
+        Ok(())
+    }
+
     // #[test]
     // fn test_the_whole_hog() -> Result<()> {
     //     std::env::set_var("RUST_LOG", "acme_micro=trace");
@@ -0,0 +1,63 @@
+//! The crate's error type.
+use crate::order::OrderStatus;
+use std::fmt;
+
+/// Top level error type.
+#[derive(Debug)]
+pub enum Error {
+    /// The ACME CA responded with an error that doesn't fit a more specific variant.
+    LetsEncryptError(String),
+    /// An HTTP request failed.
+    Io(String),
+    /// An OpenSSL operation failed.
+    Tls(String),
+    /// A response body wasn't the JSON we expected.
+    Json(String),
+    /// The order did not reach `valid` after finalization; carries the
+    /// status it ended up in.
+    OrderNotValid(OrderStatus),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::LetsEncryptError(s) => write!(f, "ACME API error: {}", s),
+            Error::Io(s) => write!(f, "I/O error: {}", s),
+            Error::Tls(s) => write!(f, "TLS error: {}", s),
+            Error::Json(s) => write!(f, "JSON error: {}", s),
+            Error::OrderNotValid(status) => write!(f, "order is in status: {:?}", status),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<openssl::error::ErrorStack> for Error {
+    fn from(e: openssl::error::ErrorStack) -> Self {
+        Error::Tls(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e.to_string())
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Io(e.to_string())
+    }
+}
+
+impl From<chrono::ParseError> for Error {
+    fn from(e: chrono::ParseError) -> Self {
+        Error::Io(e.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e.to_string())
+    }
+}
@@ -0,0 +1,26 @@
+//! A client for the ACME v2 protocol ([RFC 8555]).
+//!
+//! See [`dir::Directory`] for the entry point: load a directory, register or load an
+//! account, then drive an order through [`order`].
+//!
+//! [RFC 8555]: https://tools.ietf.org/html/rfc8555
+//! [`dir::Directory`]: dir/struct.Directory.html
+//! [`order`]: order/index.html
+#[macro_use]
+extern crate log;
+
+pub mod acc;
+pub mod api;
+pub mod cert;
+pub mod dir;
+pub mod error;
+pub mod order;
+pub mod req;
+pub mod trans;
+pub mod util;
+
+#[cfg(test)]
+pub(crate) mod test;
+
+pub use crate::acc::Account;
+pub use crate::dir::{Directory, DirectoryUrl};
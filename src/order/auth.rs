@@ -0,0 +1,95 @@
+//! Identifier authorizations and their challenges.
+use super::Identifier;
+use crate::acc::AccountInner;
+use crate::api::ApiAuth;
+use crate::api::ApiChallenge;
+use crate::error;
+use serde_json::json;
+use std::sync::Arc;
+
+/// One identifier's authorization, with the challenges that can prove it.
+pub struct Auth {
+    inner: Arc<AccountInner>,
+    api_auth: ApiAuth,
+    identifier: Identifier,
+}
+
+impl Auth {
+    pub(crate) async fn new(
+        inner: &Arc<AccountInner>,
+        api_auth: ApiAuth,
+        _url: &str,
+        identifier: Identifier,
+    ) -> Auth {
+        Auth {
+            inner: inner.clone(),
+            api_auth,
+            identifier,
+        }
+    }
+
+    /// The identifier (DNS name or IP address) this authorization is for.
+    pub fn identifier(&self) -> &Identifier {
+        &self.identifier
+    }
+
+    /// Whether this authorization is already `valid`, i.e. no challenge needs completing.
+    pub fn is_status_valid(&self) -> bool {
+        self.api_auth.status == "valid"
+    }
+
+    /// The `dns-01` challenge, if the server offered one.
+    pub fn dns_challenge(&self) -> Option<Challenge> {
+        self.challenge("dns-01")
+    }
+
+    /// The `http-01` challenge, if the server offered one.
+    pub fn http_challenge(&self) -> Option<Challenge> {
+        self.challenge("http-01")
+    }
+
+    fn challenge(&self, type_: &str) -> Option<Challenge> {
+        self.api_auth
+            .challenges
+            .iter()
+            .find(|c| c.type_ == type_)
+            .map(|c| Challenge {
+                inner: self.inner.clone(),
+                api_challenge: c.clone(),
+            })
+    }
+}
+
+/// A single challenge offered by an [`Auth`] to prove ownership of an identifier.
+pub struct Challenge {
+    inner: Arc<AccountInner>,
+    api_challenge: ApiChallenge,
+}
+
+impl Challenge {
+    /// The value to publish as a `_acme-challenge.<domain>` DNS TXT record, for `dns-01`.
+    pub async fn dns_proof(&self) -> Result<String, error::Error> {
+        let key_auth = self.key_authorization().await?;
+        let digest = openssl::hash::hash(openssl::hash::MessageDigest::sha256(), key_auth.as_bytes())?;
+        Ok(crate::util::base64url(&digest))
+    }
+
+    /// The value to serve at `http://<domain>/.well-known/acme-challenge/<token>`, for `http-01`.
+    pub async fn http_proof(&self) -> Result<String, error::Error> {
+        self.key_authorization().await
+    }
+
+    async fn key_authorization(&self) -> Result<String, error::Error> {
+        let key = self.inner.transport.signing_key().await;
+        Ok(format!("{}.{}", self.api_challenge.token, key.thumbprint()?))
+    }
+
+    /// Tell the ACME server we're ready for it to validate this challenge.
+    pub async fn validate(&self) -> Result<(), error::Error> {
+        self.inner
+            .transport
+            .call(&self.api_challenge.url, &json!({}))
+            .await?;
+        Ok(())
+    }
+}
@@ -21,16 +21,76 @@ use crate::{
     acc::AccountInner,
     api::{ApiAuth, ApiEmptyString, ApiFinalize, ApiOrder},
     cert::{create_csr, Certificate},
+    dir::{self, RevokeReason},
     error,
     util::{base64url, read_json},
 };
 use openssl::pkey::{self, PKey};
+use openssl::x509::X509;
+use serde::Deserialize;
+use std::fmt;
+use std::net::IpAddr;
 use std::{sync::Arc, time::Duration};
 
 mod auth;
 
 pub use self::auth::{Auth, Challenge};
 
+/// The status of an order, as it progresses through [RFC 8555 section 7.1.6].
+///
+/// [RFC 8555 section 7.1.6]: https://tools.ietf.org/html/rfc8555#section-7.1.6
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderStatus {
+    Pending,
+    Ready,
+    Processing,
+    Valid,
+    Invalid,
+}
+
+/// An ACME identifier: a DNS name, or (per [RFC 8738]) an IP address.
+///
+/// Orders and authorizations are built from a list of identifiers rather
+/// than bare domain strings, so that IP-address and wildcard-domain
+/// orders are routed to the right challenge and end up in the right
+/// SAN extension on the CSR.
+///
+/// [RFC 8738]: https://tools.ietf.org/html/rfc8738
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identifier {
+    /// A DNS name, e.g. `example.com` or `*.example.com`.
+    Dns(String),
+    /// An IP address, e.g. `203.0.113.1`.
+    Ip(IpAddr),
+}
+
+impl Identifier {
+    /// The ACME identifier `type`, as used in the order payload and in
+    /// each authorization's `identifier` object.
+    pub fn acme_type(&self) -> &'static str {
+        match self {
+            Identifier::Dns(_) => "dns",
+            Identifier::Ip(_) => "ip",
+        }
+    }
+
+    /// The identifier's string value, as used in the order payload and
+    /// in the CSR's subjectAltName extension.
+    pub fn value(&self) -> String {
+        match self {
+            Identifier::Dns(name) => name.clone(),
+            Identifier::Ip(addr) => addr.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
 /// The order wrapped with an outer façade.
 pub(crate) struct Order {
     inner: Arc<AccountInner>,
@@ -52,13 +112,9 @@ impl Order {
 pub(crate) async fn refresh_order(
     inner: &Arc<AccountInner>,
     url: String,
-    want_status: &'static str,
 ) -> Result<Order, error::Error> {
     let res = inner.transport.call(&url, &ApiEmptyString).await?;
-
-    // our test rig requires the order to be in `want_status`.
-    // api_order_of is different for test compilation
-    let api_order = api_order_of(res, want_status).await?;
+    let api_order: ApiOrder = read_json(res).await?;
 
     Ok(Order {
         inner: inner.clone(),
@@ -67,27 +123,6 @@ pub(crate) async fn refresh_order(
     })
 }
 
-#[cfg(not(test))]
-async fn api_order_of(
-    res: crate::req::ReqResult,
-    _want_status: &str,
-) -> Result<ApiOrder, error::Error> {
-    read_json(res).await
-}
-
-#[cfg(test)]
-// our test rig requires the order to be in `want_status`
-async fn api_order_of(
-    res: crate::req::ReqResult,
-    want_status: &str,
-) -> Result<ApiOrder, error::Error> {
-    #[allow(clippy::trivial_regex)]
-    let re = regex::Regex::new("<STATUS>").unwrap();
-    let b = re.replace_all(&res.body, want_status).to_string();
-    let api_order: ApiOrder = serde_json::from_str(&b)?;
-    Ok(api_order)
-}
-
 /// A new order created by [`Account::new_order`].
 ///
 /// An order is created using one or many domains (a primary `CN` and possible multiple
@@ -118,7 +153,35 @@ impl NewOrder {
     ///
     /// [`refresh`]: struct.NewOrder.html#method.refresh
     pub async fn is_validated(&self) -> bool {
-        self.order.api_order.is_status_ready() || self.order.api_order.is_status_valid()
+        matches!(
+            self.order.api_order.status,
+            OrderStatus::Ready | OrderStatus::Valid
+        )
+    }
+
+    /// The order's current status.
+    pub fn status(&self) -> OrderStatus {
+        self.order.api_order.status
+    }
+
+    /// The identifiers (DNS names and/or IP addresses) in this order.
+    pub fn identifiers(&self) -> Result<Vec<Identifier>, error::Error> {
+        self.order.api_order.identifiers()
+    }
+
+    /// The identifiers that still require authorization.
+    ///
+    /// Fetches each [`Auth`] (the order may mix already-valid and
+    /// not-yet-valid identifiers, e.g. when some names were authorized by
+    /// an earlier order) and returns only the identifiers whose
+    /// authorization isn't valid yet.
+    pub async fn identifiers_needing_auth(&self) -> Result<Vec<Identifier>, error::Error> {
+        let auths = self.authorizations().await?;
+        Ok(auths
+            .into_iter()
+            .filter(|auth| !auth.is_status_valid())
+            .map(|auth| auth.identifier().clone())
+            .collect())
     }
 
     /// If the order [`is_validated`] progress it to a [`CsrOrder`].
@@ -145,17 +208,19 @@ impl NewOrder {
     ///
     /// The specification calls this a "POST-as-GET" against the order URL.
     pub async fn refresh(&mut self) -> Result<(), error::Error> {
-        let order = refresh_order(&self.order.inner, self.order.url.clone(), "ready").await?;
+        let order = refresh_order(&self.order.inner, self.order.url.clone()).await?;
         self.order = order;
         Ok(())
     }
 
     /// Provide the authorizations. The number of authorizations will be the same as
-    /// the number of domains requests, i.e. at least one (the primary CN), but possibly
-    /// more (for alt names).
+    /// the number of identifiers requested, i.e. at least one (the primary CN), but
+    /// possibly more (for alt names and IP addresses).
     ///
-    /// If the order includes new domain names that have not been authorized before, this
-    /// list might contain a mix of already valid and not yet valid auths.
+    /// If the order includes new identifiers that have not been authorized before, this
+    /// list might contain a mix of already valid and not yet valid auths. Each [`Auth`]
+    /// carries the [`Identifier`] it corresponds to, so callers can route DNS and IP
+    /// identifiers to the appropriate challenge.
     pub async fn authorizations(&self) -> Result<Vec<Auth>, error::Error> {
         let mut result = vec![];
         if let Some(authorizations) = &self.order.api_order.authorizations {
@@ -167,7 +232,11 @@ impl NewOrder {
                     .call(auth_url, &ApiEmptyString)
                     .await?;
                 let api_auth: ApiAuth = read_json(res).await?;
-                result.push(Auth::new(&self.order.inner, api_auth, auth_url).await);
+                // the authorization's own `identifier` field is authoritative: RFC 8555
+                // doesn't guarantee `order.authorizations` and `order.identifiers` are
+                // in matching order.
+                let identifier = api_auth.identifier()?;
+                result.push(Auth::new(&self.order.inner, api_auth, auth_url, identifier).await);
             }
         }
         Ok(result)
@@ -235,11 +304,12 @@ impl CsrOrder {
         delay: Duration,
     ) -> Result<CertOrder, error::Error> {
         //
-        // the domains that we have authorized
-        let domains = self.order.api_order.domains();
+        // the identifiers that we have authorized
+        let identifiers = self.order.api_order.identifiers()?;
 
-        // csr from private key and authorized domains.
-        let csr = create_csr(&private_key, &domains)?;
+        // csr from private key and authorized identifiers, DNS names go into
+        // dNSName SANs and IP addresses go into iPAddress SANs.
+        let csr = create_csr(&private_key, &identifiers)?;
 
         // this is not the same as PEM.
         let csr_der = csr.to_der()?;
@@ -259,11 +329,8 @@ impl CsrOrder {
         // invalid -> the whole thing is off
         let order = wait_for_order_status(&inner, &order_url, delay).await?;
 
-        if !order.api_order.is_status_valid() {
-            return Err(error::Error::LetsEncryptError(format!(
-                "Order is in status: {:?}",
-                order.api_order.status
-            )));
+        if order.api_order.status != OrderStatus::Valid {
+            return Err(error::Error::OrderNotValid(order.api_order.status));
         }
 
         Ok(CertOrder { private_key, order })
@@ -273,6 +340,11 @@ impl CsrOrder {
     pub fn api_order(&self) -> &ApiOrder {
         &self.order.api_order
     }
+
+    /// The order's current status.
+    pub fn status(&self) -> OrderStatus {
+        self.order.api_order.status
+    }
 }
 
 async fn wait_for_order_status(
@@ -281,8 +353,8 @@ async fn wait_for_order_status(
     delay: Duration,
 ) -> Result<Order, error::Error> {
     loop {
-        let order = refresh_order(inner, url.to_string(), "valid").await?;
-        if !order.api_order.is_status_processing() {
+        let order = refresh_order(inner, url.to_string()).await?;
+        if order.api_order.status != OrderStatus::Processing {
             return Ok(order);
         }
         tokio::time::sleep(delay).await;
@@ -317,6 +389,30 @@ impl CertOrder {
         Ok(Certificate::new(pkey_pem.to_string(), cert))
     }
 
+    /// Revoke the certificate issued by this order, signed with the
+    /// account key (kid-based JWS).
+    ///
+    /// This is the preferred way to revoke a certificate right after
+    /// issuing it, since it reuses the account that requested it instead
+    /// of needing the certificate's own private key. To revoke a
+    /// certificate once the issuing account is no longer available, use
+    /// [`Directory::revoke_certificate`] instead.
+    ///
+    /// [`Directory::revoke_certificate`]: ../dir/struct.Directory.html#method.revoke_certificate
+    pub async fn revoke(self, reason: RevokeReason) -> Result<(), error::Error> {
+        let url = self
+            .order
+            .api_order
+            .certificate
+            .ok_or_else(|| error::Error::LetsEncryptError("certificate url".to_string()))?;
+        let inner = self.order.inner;
+
+        let res = inner.transport.call(&url, &ApiEmptyString).await?;
+        let cert_der = X509::from_pem(res.body.as_bytes())?.to_der()?;
+
+        dir::revoke(&inner.transport, &inner.api_directory.revokeCert, &cert_der, reason).await
+    }
+
     /// Access the underlying JSON object for debugging.
     pub fn api_order(&self) -> &ApiOrder {
         &self.order.api_order
@@ -357,6 +453,25 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_revoke() -> Result<(), error::Error> {
+        let server = crate::test::with_directory_server();
+        let url = DirectoryUrl::Other(&server.dir_url);
+        let dir = Directory::from_url(url).await?;
+        let acc = dir
+            .register_account(vec!["mailto:foo@bar.com".to_string()])
+            .await?;
+        let ord = acc.new_order("acmetest.example.com", &[]).await?;
+
+        // shortcut auth
+        let ord = CsrOrder { order: ord.order };
+        let pkey = cert::create_p256_key()?;
+        let ord = ord.finalize_pkey(pkey, Duration::from_millis(1)).await?;
+
+        ord.revoke(dir::RevokeReason::Superseded).await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_download_and_save_cert() -> Result<(), error::Error> {
         let server = crate::test::with_directory_server();
@@ -373,15 +488,44 @@ mod test {
         let ord = ord.finalize_pkey(pkey, Duration::from_millis(1)).await?;
 
         let cert = ord.download_cert().await?;
-        assert_eq!("CERT HERE", cert.certificate());
+        assert!(cert.certificate().contains("BEGIN CERTIFICATE"));
         assert!(!cert.private_key().is_empty());
-        let test_expiry = chrono::DateTime::<chrono::Utc>::from_utc(chrono::NaiveDateTime::parse_from_str("May 15 11:11:11 2015 GMT", "%h %e %H:%M:%S %Y GMT")?, chrono::Utc);
-        assert_eq!(
-            cert.expiry()?,
-            test_expiry
+        // the mock server's fixture cert expires May 15 2015.
+        let test_expiry = chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(
+            chrono::NaiveDateTime::parse_from_str(
+                "May 15 11:11:11 2015 GMT",
+                "%h %e %H:%M:%S %Y GMT",
+            )?,
+            chrono::Utc,
         );
-        
-    
+        assert_eq!(cert.expiry()?, test_expiry);
+
         Ok(())
     }
+
+    #[test]
+    fn test_identifier_acme_type_and_value() {
+        let dns = Identifier::Dns("example.com".to_string());
+        assert_eq!(dns.acme_type(), "dns");
+        assert_eq!(dns.value(), "example.com");
+        assert_eq!(dns.to_string(), "example.com");
+
+        let ip = Identifier::Ip("203.0.113.1".parse().unwrap());
+        assert_eq!(ip.acme_type(), "ip");
+        assert_eq!(ip.value(), "203.0.113.1");
+    }
+
+    #[test]
+    fn test_order_status_deserialize() {
+        let s: OrderStatus = serde_json::from_str(r#""pending""#).unwrap();
+        assert_eq!(s, OrderStatus::Pending);
+        let s: OrderStatus = serde_json::from_str(r#""ready""#).unwrap();
+        assert_eq!(s, OrderStatus::Ready);
+        let s: OrderStatus = serde_json::from_str(r#""processing""#).unwrap();
+        assert_eq!(s, OrderStatus::Processing);
+        let s: OrderStatus = serde_json::from_str(r#""valid""#).unwrap();
+        assert_eq!(s, OrderStatus::Valid);
+        let s: OrderStatus = serde_json::from_str(r#""invalid""#).unwrap();
+        assert_eq!(s, OrderStatus::Invalid);
+    }
 }
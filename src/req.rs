@@ -0,0 +1,67 @@
+//! Thin wrapper around the HTTP calls the ACME API needs.
+use crate::error;
+use std::collections::HashMap;
+
+/// The parts of an HTTP response the rest of the crate cares about.
+pub struct ReqResult {
+    pub status: u16,
+    pub body: String,
+    headers: HashMap<String, String>,
+}
+
+async fn to_result(res: reqwest::Response) -> Result<ReqResult, error::Error> {
+    let status = res.status().as_u16();
+    let headers = res
+        .headers()
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.as_str().to_ascii_lowercase(),
+                v.to_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect();
+    let body = res.text().await?;
+    if !(200..300).contains(&status) {
+        return Err(error::Error::LetsEncryptError(format!(
+            "HTTP {}: {}",
+            status, body
+        )));
+    }
+    Ok(ReqResult {
+        status,
+        body,
+        headers,
+    })
+}
+
+/// GET a URL, e.g. to fetch the directory or ARI suggested window.
+pub async fn req_get(url: &str) -> Result<ReqResult, error::Error> {
+    let res = reqwest::Client::new().get(url).send().await?;
+    to_result(res).await
+}
+
+/// HEAD a URL, e.g. to fetch a fresh anti-replay nonce.
+pub async fn req_head(url: &str) -> Result<ReqResult, error::Error> {
+    let res = reqwest::Client::new().head(url).send().await?;
+    to_result(res).await
+}
+
+/// POST a JOSE JSON body to a URL.
+pub async fn req_post(url: &str, body: String) -> Result<ReqResult, error::Error> {
+    let res = reqwest::Client::new()
+        .post(url)
+        .header("content-type", "application/jose+json")
+        .body(body)
+        .send()
+        .await?;
+    to_result(res).await
+}
+
+/// Pull a header out of a response, erroring if it's missing.
+pub fn req_expect_header(res: &ReqResult, name: &str) -> Result<String, error::Error> {
+    res.headers
+        .get(&name.to_ascii_lowercase())
+        .cloned()
+        .ok_or_else(|| error::Error::LetsEncryptError(format!("missing '{}' header", name)))
+}
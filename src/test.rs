@@ -0,0 +1,145 @@
+//! An in-process ACME server used by the other modules' tests.
+use openssl::asn1::Asn1Time;
+use openssl::hash::MessageDigest;
+use serde_json::json;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use tiny_http::{Header, Method, Response, Server};
+
+/// A running mock ACME directory server. Dropping it does not stop the
+/// server: its background thread serves until the test process exits.
+pub(crate) struct TestServer {
+    pub(crate) dir_url: String,
+}
+
+/// Start a mock ACME server on a background thread and return its directory URL.
+pub(crate) fn with_directory_server() -> TestServer {
+    let server = Server::http("127.0.0.1:0").expect("bind mock ACME server");
+    let base = format!("http://{}", server.server_addr());
+    let dir_url = format!("{}/directory", base);
+
+    std::thread::spawn(move || {
+        let finalized = AtomicBool::new(false);
+        for request in server.incoming_requests() {
+            handle(request, &base, &finalized);
+        }
+    });
+
+    TestServer { dir_url }
+}
+
+fn handle(mut request: tiny_http::Request, base: &str, finalized: &AtomicBool) {
+    let method = request.method().clone();
+    let url = request.url().to_string();
+    let mut body = String::new();
+    let _ = request.as_reader().read_to_string(&mut body);
+
+    let (status, headers, resp_body) = route(&method, &url, base, finalized);
+
+    let mut response = Response::from_string(resp_body).with_status_code(status);
+    for (name, value) in headers {
+        if let Ok(header) = Header::from_bytes(name.as_bytes(), value.as_bytes()) {
+            response = response.with_header(header);
+        }
+    }
+    let _ = request.respond(response);
+}
+
+fn route(
+    method: &Method,
+    url: &str,
+    base: &str,
+    finalized: &AtomicBool,
+) -> (u16, Vec<(String, String)>, String) {
+    let nonce_header = || ("Replay-Nonce".to_string(), "mock-nonce".to_string());
+
+    match (method, url) {
+        (Method::Get, "/directory") => (
+            200,
+            vec![],
+            json!({
+                "newNonce": format!("{}/new-nonce", base),
+                "newAccount": format!("{}/new-account", base),
+                "newOrder": format!("{}/new-order", base),
+                "revokeCert": format!("{}/revoke-cert", base),
+                "keyChange": format!("{}/key-change", base),
+                "renewalInfo": format!("{}/renewal-info", base),
+            })
+            .to_string(),
+        ),
+        (Method::Head, "/new-nonce") => (200, vec![nonce_header()], String::new()),
+        (Method::Post, "/new-account") => (
+            201,
+            vec![
+                nonce_header(),
+                ("Location".to_string(), format!("{}/acct/1", base)),
+            ],
+            json!({ "status": "valid" }).to_string(),
+        ),
+        (Method::Post, "/new-order") => (
+            201,
+            vec![
+                nonce_header(),
+                ("Location".to_string(), format!("{}/order/1", base)),
+            ],
+            order_body(base, finalized),
+        ),
+        (Method::Post, "/order/1") => (200, vec![nonce_header()], order_body(base, finalized)),
+        (Method::Post, "/order/1/finalize") => {
+            finalized.store(true, Ordering::SeqCst);
+            (200, vec![nonce_header()], order_body(base, finalized))
+        }
+        (Method::Post, "/authz/1") => (
+            200,
+            vec![nonce_header()],
+            json!({
+                "status": "pending",
+                "identifier": { "type": "dns", "value": "acmetest.example.com" },
+                "challenges": [{
+                    "type": "dns-01",
+                    "url": format!("{}/chall/1", base),
+                    "token": "mock-token",
+                    "status": "pending",
+                }],
+            })
+            .to_string(),
+        ),
+        (Method::Post, "/cert/1") => (200, vec![nonce_header()], mock_cert_pem()),
+        (Method::Post, "/revoke-cert") => (200, vec![nonce_header()], String::new()),
+        (Method::Post, "/key-change") => (200, vec![nonce_header()], String::new()),
+        _ => (404, vec![], String::new()),
+    }
+}
+
+fn order_body(base: &str, finalized: &AtomicBool) -> String {
+    let done = finalized.load(Ordering::SeqCst);
+    json!({
+        "status": if done { "valid" } else { "ready" },
+        "identifiers": [{ "type": "dns", "value": "acmetest.example.com" }],
+        "authorizations": [format!("{}/authz/1", base)],
+        "finalize": format!("{}/order/1/finalize", base),
+        "certificate": if done { Some(format!("{}/cert/1", base)) } else { None },
+    })
+    .to_string()
+}
+
+/// A self-signed leaf with a fixed `notAfter`, so tests can assert on `expiry()`.
+fn mock_cert_pem() -> String {
+    static CERT: OnceLock<String> = OnceLock::new();
+    CERT.get_or_init(|| {
+        let key = crate::cert::create_p256_key().expect("generate mock leaf key");
+        let mut builder = openssl::x509::X509Builder::new().expect("new x509 builder");
+        builder.set_version(2).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::from_str_x509("20150515111111Z").unwrap())
+            .unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+        let x509 = builder.build();
+        String::from_utf8(x509.to_pem().unwrap()).unwrap()
+    })
+    .clone()
+}
@@ -0,0 +1,158 @@
+//! Signed transport: anti-replay nonces and JWS-wrapped calls.
+use crate::acc::AcmeKey;
+use crate::error;
+use crate::req::{req_expect_header, req_head, req_post, ReqResult};
+use crate::util::base64url;
+use serde::Serialize;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Pool of anti-replay nonces handed out by the ACME server's `newNonce` endpoint.
+///
+/// Every signed request consumes one nonce and every response hands back a fresh
+/// one (in the `Replay-Nonce` header), so in steady state we rarely need to make
+/// an extra round trip just to get a nonce.
+pub struct NoncePool {
+    new_nonce_url: String,
+    pool: RwLock<Vec<String>>,
+}
+
+impl NoncePool {
+    pub async fn new(new_nonce_url: &str) -> NoncePool {
+        let pool = NoncePool {
+            new_nonce_url: new_nonce_url.to_string(),
+            pool: RwLock::new(Vec::new()),
+        };
+        // Best-effort warm start; if this fails we just fetch lazily on first use.
+        if let Ok(nonce) = pool.fetch().await {
+            pool.pool.write().await.push(nonce);
+        }
+        pool
+    }
+
+    async fn fetch(&self) -> Result<String, error::Error> {
+        let res = req_head(&self.new_nonce_url).await?;
+        req_expect_header(&res, "replay-nonce")
+    }
+
+    async fn get(&self) -> Result<String, error::Error> {
+        if let Some(nonce) = self.pool.write().await.pop() {
+            return Ok(nonce);
+        }
+        self.fetch().await
+    }
+
+    async fn stash(&self, nonce: String) {
+        self.pool.write().await.push(nonce);
+    }
+}
+
+/// Account-authenticated transport: signs requests as JWS and tracks the
+/// account's key id (kid) once the account has one.
+pub struct Transport {
+    nonce_pool: Arc<NoncePool>,
+    acme_key: RwLock<AcmeKey>,
+    key_id: RwLock<Option<String>>,
+}
+
+impl Transport {
+    pub async fn new(nonce_pool: &Arc<NoncePool>, acme_key: AcmeKey) -> Transport {
+        Transport {
+            nonce_pool: nonce_pool.clone(),
+            acme_key: RwLock::new(acme_key),
+            key_id: RwLock::new(None),
+        }
+    }
+
+    /// Record the key id (kid) the server handed back for this account, so
+    /// later calls can use [`call`] instead of [`call_jwk`].
+    ///
+    /// [`call`]: #method.call
+    /// [`call_jwk`]: #method.call_jwk
+    pub async fn set_key_id(&self, kid: String) {
+        *self.key_id.write().await = Some(kid);
+    }
+
+    pub(crate) async fn key_id(&self) -> Option<String> {
+        self.key_id.read().await.clone()
+    }
+
+    pub(crate) async fn has_key_id(&self) -> bool {
+        self.key_id.read().await.is_some()
+    }
+
+    pub(crate) async fn signing_key(&self) -> AcmeKey {
+        self.acme_key.read().await.clone()
+    }
+
+    /// Swap the signing key, e.g. after a successful `Account::change_key` rollover.
+    pub(crate) async fn set_signing_key(&self, new_key: AcmeKey) {
+        *self.acme_key.write().await = new_key;
+    }
+
+    /// An authenticated call signed with the account's key id (kid). The account
+    /// must already have a key id (see [`set_key_id`]).
+    ///
+    /// [`set_key_id`]: #method.set_key_id
+    pub async fn call<T: Serialize>(
+        &self,
+        url: &str,
+        payload: &T,
+    ) -> Result<ReqResult, error::Error> {
+        let kid = self.key_id().await.ok_or_else(|| {
+            error::Error::LetsEncryptError("transport has no key id set".to_string())
+        })?;
+        let acme_key = self.acme_key.read().await;
+        let protected = json!({
+            "alg": acme_key.signing_algorithm(),
+            "kid": kid,
+            "nonce": self.nonce_pool.get().await?,
+            "url": url,
+        });
+        self.send_signed(&acme_key, url, protected, payload).await
+    }
+
+    /// An authenticated call signed with the account key's public JWK, used
+    /// before the account has a key id (`newAccount`).
+    pub async fn call_jwk<T: Serialize>(
+        &self,
+        url: &str,
+        payload: &T,
+    ) -> Result<ReqResult, error::Error> {
+        let acme_key = self.acme_key.read().await;
+        let protected = json!({
+            "alg": acme_key.signing_algorithm(),
+            "jwk": acme_key.to_public_jwk()?,
+            "nonce": self.nonce_pool.get().await?,
+            "url": url,
+        });
+        self.send_signed(&acme_key, url, protected, payload).await
+    }
+
+    async fn send_signed<T: Serialize>(
+        &self,
+        acme_key: &AcmeKey,
+        url: &str,
+        protected: serde_json::Value,
+        payload: &T,
+    ) -> Result<ReqResult, error::Error> {
+        let protected_b64 = base64url(protected.to_string().as_bytes());
+        let payload_b64 = base64url(serde_json::to_string(payload)?.as_bytes());
+        let signing_input = format!("{}.{}", protected_b64, payload_b64);
+        let signature = acme_key.sign(signing_input.as_bytes())?;
+
+        let body = json!({
+            "protected": protected_b64,
+            "payload": payload_b64,
+            "signature": base64url(&signature),
+        })
+        .to_string();
+
+        let res = req_post(url, body).await?;
+        if let Ok(nonce) = req_expect_header(&res, "replay-nonce") {
+            self.nonce_pool.stash(nonce).await;
+        }
+        Ok(res)
+    }
+}
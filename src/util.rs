@@ -0,0 +1,16 @@
+//! Small shared helpers.
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use crate::error;
+use crate::req::ReqResult;
+use serde::de::DeserializeOwned;
+
+/// Base64url encode (no padding), as used throughout JOSE/ACME.
+pub fn base64url(input: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(input)
+}
+
+/// Deserialize a response body as JSON.
+pub async fn read_json<T: DeserializeOwned>(res: ReqResult) -> Result<T, error::Error> {
+    Ok(serde_json::from_str(&res.body)?)
+}